@@ -0,0 +1,284 @@
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// Entry field that can be copied to the clipboard instead of printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ClipField {
+    Username,
+    Password,
+    Notes,
+}
+
+impl ClipField {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ClipField::Username => "username",
+            ClipField::Password => "password",
+            ClipField::Notes => "notes",
+        }
+    }
+}
+
+/// A clipboard backend capable of reading and writing the system clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    #[cfg(feature = "arboard")]
+    Arboard,
+    Xclip,
+    Xsel,
+    WlCopy,
+    Pbcopy,
+}
+
+impl Backend {
+    /// Stable name used to hand the backend to the re-exec'd clear daemon.
+    fn as_arg(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "arboard")]
+            Backend::Arboard => "arboard",
+            Backend::Xclip => "xclip",
+            Backend::Xsel => "xsel",
+            Backend::WlCopy => "wl-copy",
+            Backend::Pbcopy => "pbcopy",
+        }
+    }
+
+    fn from_arg(s: &str) -> Option<Backend> {
+        match s {
+            #[cfg(feature = "arboard")]
+            "arboard" => Some(Backend::Arboard),
+            "xclip" => Some(Backend::Xclip),
+            "xsel" => Some(Backend::Xsel),
+            "wl-copy" => Some(Backend::WlCopy),
+            "pbcopy" => Some(Backend::Pbcopy),
+            _ => None,
+        }
+    }
+}
+
+/// Finds an available clipboard backend, preferring a compiled-in crate
+/// over shelling out to a platform binary discovered on `$PATH`.
+fn detect_backend() -> Option<Backend> {
+    #[cfg(feature = "arboard")]
+    {
+        return Some(Backend::Arboard);
+    }
+
+    #[cfg(not(feature = "arboard"))]
+    {
+        for (bin, backend) in [
+            ("wl-copy", Backend::WlCopy),
+            ("xclip", Backend::Xclip),
+            ("xsel", Backend::Xsel),
+            ("pbcopy", Backend::Pbcopy),
+        ] {
+            if on_path(bin) {
+                return Some(backend);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(not(feature = "arboard"))]
+fn on_path(bin: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(bin).is_file()))
+        .unwrap_or(false)
+}
+
+#[cfg(feature = "arboard")]
+fn to_io_err(err: arboard::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, err.to_string())
+}
+
+fn copy(backend: Backend, text: &str) -> io::Result<()> {
+    #[cfg(feature = "arboard")]
+    if let Backend::Arboard = backend {
+        use arboard::Clipboard;
+        let mut clipboard = Clipboard::new().map_err(to_io_err)?;
+        clipboard.set_text(text.to_owned()).map_err(to_io_err)?;
+        return Ok(());
+    }
+
+    let (bin, args): (&str, &[&str]) = match backend {
+        Backend::Xclip => ("xclip", &["-selection", "clipboard"]),
+        Backend::Xsel => ("xsel", &["--clipboard", "--input"]),
+        Backend::WlCopy => ("wl-copy", &[]),
+        Backend::Pbcopy => ("pbcopy", &[]),
+        #[cfg(feature = "arboard")]
+        Backend::Arboard => unreachable!(),
+    };
+
+    let mut child = Command::new(bin)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .expect("piped stdin")
+        .write_all(text.as_bytes())?;
+    child.wait()?;
+    Ok(())
+}
+
+fn paste(backend: Backend) -> io::Result<String> {
+    #[cfg(feature = "arboard")]
+    if let Backend::Arboard = backend {
+        use arboard::Clipboard;
+        let mut clipboard = Clipboard::new().map_err(to_io_err)?;
+        return clipboard.get_text().map_err(to_io_err);
+    }
+
+    let (bin, args): (&str, &[&str]) = match backend {
+        Backend::Xclip => ("xclip", &["-selection", "clipboard", "-o"]),
+        Backend::Xsel => ("xsel", &["--clipboard", "--output"]),
+        Backend::WlCopy => ("wl-paste", &["-n"]),
+        Backend::Pbcopy => ("pbpaste", &[]),
+        #[cfg(feature = "arboard")]
+        Backend::Arboard => unreachable!(),
+    };
+
+    let output = Command::new(bin).args(args).output()?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Argument used to recognize a re-exec'd clear-daemon process. Checked by
+/// `main` before argument parsing since it isn't part of the public CLI.
+pub const CLEAR_DAEMON_ARG: &str = "__clipboard-clear-daemon";
+
+/// Copies `value` to the clipboard and prints a confirmation in place of
+/// the secret. Unless `timeout` is `0`, schedules a detached background
+/// process to restore whatever was previously on the clipboard once the
+/// timeout elapses, but only if the clipboard still holds our secret at
+/// that point (so we don't clobber something the user copied meanwhile).
+///
+/// Returns an error (and copies nothing) when no clipboard backend is
+/// available, so callers can fall back to printing the value instead.
+pub fn copy_with_auto_clear(field_label: &str, value: &str, timeout: u64) -> io::Result<()> {
+    let backend = detect_backend()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no clipboard backend available"))?;
+
+    let previous = paste(backend).unwrap_or_default();
+    copy(backend, value)?;
+
+    if timeout > 0 {
+        spawn_clear_daemon(backend, value, &previous, timeout)?;
+        println!("copied {field_label}, clears in {timeout}s");
+    } else {
+        println!("copied {field_label}");
+    }
+
+    Ok(())
+}
+
+/// Writes `value` to `w` as a 8-byte little-endian length prefix followed
+/// by its raw bytes, so multi-line values (e.g. the Notes field) round-trip
+/// intact instead of being truncated at the first newline.
+fn write_framed(w: &mut impl Write, value: &str) -> io::Result<()> {
+    let bytes = value.as_bytes();
+    w.write_all(&(bytes.len() as u64).to_le_bytes())?;
+    w.write_all(bytes)?;
+    Ok(())
+}
+
+/// Reads a value written by [`write_framed`] back off of `r`.
+fn read_framed(r: &mut impl Read) -> io::Result<String> {
+    let mut len_buf = [0u8; 8];
+    r.read_exact(&mut len_buf)?;
+    let len = u64::from_le_bytes(len_buf) as usize;
+
+    let mut buf = vec![0u8; len];
+    r.read_exact(&mut buf)?;
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+/// Spawns a detached copy of the current binary in clear-daemon mode,
+/// handing it the secret and the previous clipboard contents over stdin
+/// so neither shows up in `ps`.
+fn spawn_clear_daemon(backend: Backend, secret: &str, previous: &str, timeout: u64) -> io::Result<()> {
+    let exe = std::env::current_exe()?;
+    let mut child = Command::new(exe)
+        .arg(CLEAR_DAEMON_ARG)
+        .arg(backend.as_arg())
+        .arg(timeout.to_string())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let mut stdin = child.stdin.take().expect("piped stdin");
+    write_framed(&mut stdin, secret)?;
+    write_framed(&mut stdin, previous)?;
+    drop(stdin);
+
+    Ok(())
+}
+
+/// Entry point for the re-exec'd clear-daemon process (`argv[1] ==
+/// CLEAR_DAEMON_ARG`). Reads the secret and the previous clipboard
+/// contents from stdin, sleeps for `timeout` seconds, then restores the
+/// previous contents only if the clipboard still holds our secret.
+pub fn run_clear_daemon(args: &[String]) -> io::Result<()> {
+    let backend = args
+        .first()
+        .and_then(|s| Backend::from_arg(s))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "unknown clipboard backend"))?;
+    let timeout: u64 = args
+        .get(1)
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid timeout"))?;
+
+    let mut stdin = io::stdin();
+    let secret = read_framed(&mut stdin)?;
+    let previous = read_framed(&mut stdin)?;
+
+    thread::sleep(Duration::from_secs(timeout));
+
+    if paste(backend).unwrap_or_default() == secret {
+        copy(backend, &previous)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn framed_value_round_trips() {
+        let mut buf = Cursor::new(Vec::new());
+        write_framed(&mut buf, "hunter2").unwrap();
+
+        buf.set_position(0);
+        assert_eq!(read_framed(&mut buf).unwrap(), "hunter2");
+    }
+
+    #[test]
+    fn framed_multiline_value_round_trips() {
+        let notes = "line one\nline two\nline three";
+        let mut buf = Cursor::new(Vec::new());
+        write_framed(&mut buf, notes).unwrap();
+
+        buf.set_position(0);
+        assert_eq!(read_framed(&mut buf).unwrap(), notes);
+    }
+
+    #[test]
+    fn consecutive_framed_values_round_trip_independently() {
+        let mut buf = Cursor::new(Vec::new());
+        write_framed(&mut buf, "secret\nvalue").unwrap();
+        write_framed(&mut buf, "previous").unwrap();
+
+        buf.set_position(0);
+        assert_eq!(read_framed(&mut buf).unwrap(), "secret\nvalue");
+        assert_eq!(read_framed(&mut buf).unwrap(), "previous");
+    }
+}
@@ -1,7 +1,14 @@
+mod clipboard;
+mod output;
+mod search;
+
 use clap::Parser;
+use clipboard::ClipField;
 use console::{style, Term};
-use dialoguer::{theme::ColorfulTheme, Password, Select};
-use keepass::{Database, Entry, Group, Node, NodeRef, Result};
+use dialoguer::{theme::ColorfulTheme, FuzzySelect, Password};
+use keepass::{Database, Entry, Error, Group, Node, NodeRef, Result};
+use output::{Field, OutputFormat};
+use search::{Matcher, SearchFields};
 use std::fs::File;
 
 /// KeePass CLI
@@ -11,7 +18,6 @@ struct Args {
     db: String,
 
     /// Searches an entry that matches the title that is given
-    #[clap(requires = "password")]
     entry_title: Option<String>,
 
     /// Path to keyfile
@@ -21,6 +27,50 @@ struct Args {
     /// Password
     #[clap(short, long)]
     password: Option<String>,
+
+    /// Reads the master password from stdin (a single line, trailing newline trimmed)
+    #[clap(long)]
+    password_stdin: bool,
+
+    /// Matches `entry_title` as a regular expression instead of a
+    /// case-insensitive substring
+    #[clap(long)]
+    regex: bool,
+
+    /// Also match the username field
+    #[clap(long)]
+    with_username: bool,
+
+    /// Also match the URL field
+    #[clap(long)]
+    with_url: bool,
+
+    /// Also match the notes field
+    #[clap(long)]
+    with_note: bool,
+
+    /// Output format for the title-search path
+    #[clap(long, value_enum, default_value = "text")]
+    format: OutputFormat,
+
+    /// Print only this field of a single matched entry, with no label or
+    /// decoration, e.g. `PW=$(keepass-cli db.kdbx "GitHub" -p "$pw" --field password)`
+    #[clap(long, value_enum, requires = "entry_title")]
+    field: Option<Field>,
+
+    /// Which match to use when the search turns up more than one entry
+    /// (0-based); used by `--field` and to pick the entry copied to the
+    /// clipboard in the text output path
+    #[clap(long)]
+    index: Option<usize>,
+
+    /// Entry field to copy to the clipboard instead of printing
+    #[clap(long, value_enum, default_value = "password")]
+    clip_field: ClipField,
+
+    /// Seconds before the clipboard is cleared and restored (0 disables auto-clear)
+    #[clap(long, default_value_t = 10)]
+    clip_timeout: u64,
 }
 
 struct Selection<'a> {
@@ -47,40 +97,104 @@ struct Context<'a> {
 }
 
 fn main() -> Result<()> {
+    let raw_args: Vec<String> = std::env::args().collect();
+    if raw_args.get(1).map(String::as_str) == Some(clipboard::CLEAR_DAEMON_ARG) {
+        let _ = clipboard::run_clear_daemon(&raw_args[2..]);
+        return Ok(());
+    }
+
     let args = Args::parse();
     let term = Term::stderr();
 
-    // TODO: handle no-password databases
-    let password = if let Some(password) = args.password {
-        password
-    } else {
-        Password::with_theme(&ColorfulTheme::default())
-            .with_prompt("Password")
-            .allow_empty_password(true)
-            .interact_on(&term)
-            .unwrap()
-    };
-
     // Open KeePass database
-    let path = std::path::Path::new(&args.db);
-    // TODO: trigger the reprompt of password if it's entered incorrectly
-    // TODO: if password is passed in by args, we should panic
-    let db = Database::open(&mut File::open(path)?, Some(&password), None)?;
+    let db = open_database(&args, &term)?;
 
     // if we have some entry_title, then we want to only print and don't prompt anything
-    if let Some(entry_title) = args.entry_title {
-        let search_result = search_entry_by_title(&entry_title, &db.root);
-        if search_result.len() == 0 {
-            println!("No entries found");
-        } else {
-            println!(
-                "Found {} result(s) for title name \"{}\"",
-                search_result.len(),
-                entry_title
-            );
-            for entry in search_result {
-                print_entry(entry);
-                println!();
+    if let Some(query) = &args.entry_title {
+        let matcher = match Matcher::new(query, args.regex) {
+            Ok(matcher) => matcher,
+            Err(err) => {
+                eprintln!("Invalid --regex pattern: {err}");
+                std::process::exit(1);
+            }
+        };
+        let fields = SearchFields {
+            username: args.with_username,
+            url: args.with_url,
+            notes: args.with_note,
+        };
+        let search_result = search::find_entries(&matcher, fields, &db.root);
+
+        if let Some(field) = args.field {
+            let entry = match args.index {
+                Some(index) => search_result.get(index).map(|found| found.entry),
+                None if search_result.len() == 1 => Some(search_result[0].entry),
+                None => {
+                    eprintln!(
+                        "--field matched {} entries; pass --index to disambiguate",
+                        search_result.len()
+                    );
+                    std::process::exit(1);
+                }
+            };
+
+            return match entry.and_then(|entry| field.read(entry)) {
+                Some(value) => {
+                    println!("{value}");
+                    Ok(())
+                }
+                None => {
+                    eprintln!("no matching entry with that field set");
+                    std::process::exit(1);
+                }
+            };
+        }
+
+        match args.format {
+            OutputFormat::Json => {
+                if let Err(err) = output::print_json(&search_result) {
+                    eprintln!("Failed to serialize results: {err}");
+                    std::process::exit(1);
+                }
+            }
+            OutputFormat::Text => {
+                if search_result.is_empty() {
+                    println!("No entries found");
+                } else {
+                    println!(
+                        "Found {} result(s) for title name \"{}\"",
+                        search_result.len(),
+                        query
+                    );
+
+                    // Copying to the clipboard only makes sense for a single
+                    // entry; with several matches, require --index to pick
+                    // one rather than clobbering the clipboard in a loop.
+                    let selected = match args.index {
+                        Some(index) => match search_result.get(index) {
+                            Some(found) => Some(found),
+                            None => {
+                                eprintln!(
+                                    "--index out of range ({} match(es) found)",
+                                    search_result.len()
+                                );
+                                std::process::exit(1);
+                            }
+                        },
+                        None if search_result.len() == 1 => Some(&search_result[0]),
+                        None => None,
+                    };
+
+                    match selected {
+                        Some(found) => print_entry(found.entry, args.clip_field, args.clip_timeout),
+                        None => {
+                            println!("(multiple matches; pass --index to copy one to the clipboard)");
+                            for found in &search_result {
+                                println!("  {}", found.entry.get_title().unwrap_or_default());
+                            }
+                        }
+                    }
+                }
             }
         }
     } else {
@@ -91,12 +205,110 @@ fn main() -> Result<()> {
 
         let mut context: Vec<Context> = vec![root_context];
 
-        prompt(&term, &db.root, &mut context);
+        prompt(&term, &db.root, &mut context, args.clip_field, args.clip_timeout);
     }
     Ok(())
 }
 
-fn prompt<'a>(term: &Term, node: &'a Group, context: &'a mut Vec<Context<'a>>) {
+/// Opens the database at `args.db`, using `args.keyfile` and/or
+/// `args.password` as configured.
+///
+/// - When the password comes from `--password`, `--password-stdin`, or the
+///   `KEEPASS_PASSWORD` environment variable, a decryption error is
+///   returned immediately (the caller isn't going to get a better password
+///   by retrying).
+/// - Otherwise the user is prompted interactively, up to three attempts in
+///   all, re-prompting after a wrong password before giving up. Only a wrong-key error
+///   triggers a retry; IO errors, missing files, and corrupt databases are
+///   returned immediately. The interactive prompt only fires when stdin is
+///   an attached terminal; with no terminal and no non-interactive password
+///   source, this fails with a clear error.
+// TODO: handle databases that need neither a password nor a keyfile
+fn open_database(args: &Args, term: &Term) -> Result<Database> {
+    let path = std::path::Path::new(&args.db);
+
+    let open_once = |password: &str| -> Result<Database> {
+        let mut keyfile = args.keyfile.as_ref().map(File::open).transpose()?;
+        // An empty password means none was supplied (e.g. a keyfile-only
+        // database, left blank at the prompt) — pass `None` rather than
+        // forcing an empty-password component into the composite key.
+        let password = if password.is_empty() { None } else { Some(password) };
+        Database::open(
+            &mut File::open(path)?,
+            password,
+            keyfile.as_mut().map(|f| f as &mut dyn std::io::Read),
+        )
+    };
+
+    if let Some(password) = non_interactive_password(args)? {
+        return open_once(&password);
+    }
+
+    if !Term::stdin().is_term() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "no terminal attached; supply a password with --password, --password-stdin, or the KEEPASS_PASSWORD environment variable",
+        )
+        .into());
+    }
+
+    const MAX_ATTEMPTS: u32 = 3;
+    for attempt in 1..=MAX_ATTEMPTS {
+        let password = Password::with_theme(&ColorfulTheme::default())
+            .with_prompt("Password")
+            .allow_empty_password(true)
+            .interact_on(term)
+            .unwrap();
+
+        match open_once(&password) {
+            Ok(db) => return Ok(db),
+            Err(err) if is_wrong_key_error(&err) && attempt < MAX_ATTEMPTS => {
+                eprintln!("Wrong password, try again ({attempt}/{MAX_ATTEMPTS})");
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    unreachable!("loop always returns within MAX_ATTEMPTS iterations")
+}
+
+/// True when `err` indicates a wrong password/keyfile rather than an
+/// unrelated IO, format, or corruption error — only the former is worth
+/// re-prompting for.
+fn is_wrong_key_error(err: &Error) -> bool {
+    matches!(err, Error::IncorrectKey)
+}
+
+/// Resolves a password from a non-interactive source, in order of
+/// precedence: `--password`, `--password-stdin`, then the
+/// `KEEPASS_PASSWORD` environment variable. Returns `None` when none of
+/// these were supplied, so the caller can fall back to an interactive
+/// prompt.
+fn non_interactive_password(args: &Args) -> Result<Option<String>> {
+    if let Some(password) = &args.password {
+        return Ok(Some(password.clone()));
+    }
+
+    if args.password_stdin {
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        return Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()));
+    }
+
+    if let Ok(password) = std::env::var("KEEPASS_PASSWORD") {
+        return Ok(Some(password));
+    }
+
+    Ok(None)
+}
+
+fn prompt<'a>(
+    term: &Term,
+    node: &'a Group,
+    context: &'a mut Vec<Context<'a>>,
+    clip_field: ClipField,
+    clip_timeout: u64,
+) {
     let selections = node
         .children
         .iter()
@@ -129,7 +341,9 @@ fn prompt<'a>(term: &Term, node: &'a Group, context: &'a mut Vec<Context<'a>>) {
         None => 0,
     };
 
-    let selection = Select::with_theme(&ColorfulTheme::default())
+    // FuzzySelect lets the user type to narrow `selections` by fuzzy-matching
+    // group/entry names, instead of arrowing through the whole list.
+    let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
         .with_prompt(format!("{} {}", prompt_message, styled_hint))
         .default(last_selected_index)
         .items(&selections[..])
@@ -153,12 +367,12 @@ fn prompt<'a>(term: &Term, node: &'a Group, context: &'a mut Vec<Context<'a>>) {
                     // if we select a group, then we want to push the selection context,
                     // and trigger another prompt to the user
                     context.push(Context { node: g, index: 0 });
-                    prompt(term, g, context)
+                    prompt(term, g, context, clip_field, clip_timeout)
                 }
                 NodeRef::Entry(e) => {
-                    print_entry(e);
+                    print_entry(e, clip_field, clip_timeout);
                     println!();
-                    prompt(term, context.last().unwrap().node, context)
+                    prompt(term, context.last().unwrap().node, context, clip_field, clip_timeout)
                 }
             }
         }
@@ -168,7 +382,7 @@ fn prompt<'a>(term: &Term, node: &'a Group, context: &'a mut Vec<Context<'a>>) {
             // with the last context
             let _ = context.pop();
             if let Some(prev_group) = context.last() {
-                prompt(term, prev_group.node, context)
+                prompt(term, prev_group.node, context, clip_field, clip_timeout)
             } else {
                 println!();
                 println!("END")
@@ -177,29 +391,42 @@ fn prompt<'a>(term: &Term, node: &'a Group, context: &'a mut Vec<Context<'a>>) {
     };
 }
 
-fn print_entry(entry: &Entry) {
+/// Prints the entry's title, then copies `clip_field` to the clipboard
+/// (auto-clearing after `clip_timeout` seconds) instead of printing the
+/// secret value. Falls back to printing the value if no clipboard backend
+/// is available.
+fn print_entry(entry: &Entry, clip_field: ClipField, clip_timeout: u64) {
     println!("{}", style(entry.get_title().unwrap()).italic());
-    println!("  👤: {}", style(entry.get_username().unwrap()).bold());
-    println!("  🔑: {}", style(entry.get_password().unwrap()).bold());
-    let notes = entry.get("Notes");
-    if let Some(note) = notes {
-        if note.len() > 0 {
-            println!("  📝: {}", note);
+
+    let value = match clip_field {
+        ClipField::Username => entry.get_username(),
+        ClipField::Password => entry.get_password(),
+        ClipField::Notes => entry.get("Notes"),
+    };
+
+    match value {
+        Some(value) if !value.is_empty() => {
+            if let Err(err) = clipboard::copy_with_auto_clear(clip_field.label(), value, clip_timeout) {
+                eprintln!("  (clipboard unavailable: {err}, printing instead)");
+                println!("  {}: {}", clip_field.label(), style(value).bold());
+            }
         }
+        _ => println!("  (no {} set)", clip_field.label()),
     }
 }
 
-fn search_entry_by_title<'a>(title: &'a str, root_node: &'a Group) -> Vec<&'a Entry> {
-    let mut result: Vec<&Entry> = vec![];
-    for node in root_node {
-        match node {
-            NodeRef::Entry(e) => {
-                if e.get_title().unwrap() == title {
-                    result.push(e)
-                }
-            }
-            _ => {}
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn incorrect_key_is_a_wrong_key_error() {
+        assert!(is_wrong_key_error(&Error::IncorrectKey));
+    }
+
+    #[test]
+    fn io_error_is_not_a_wrong_key_error() {
+        let io_err = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+        assert!(!is_wrong_key_error(&Error::from(io_err)));
     }
-    return result;
 }
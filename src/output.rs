@@ -0,0 +1,88 @@
+use crate::search::FoundEntry;
+use keepass::Entry;
+use serde::Serialize;
+
+/// Output mode for the title-search path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// A single entry field, selectable with `--field` for non-interactive
+/// retrieval of one raw value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Field {
+    Title,
+    Username,
+    Password,
+    Url,
+    Notes,
+}
+
+impl Field {
+    /// Reads this field's raw value off of `entry`, with no label or
+    /// decoration, suitable for `$(...)` capture in a shell script.
+    pub fn read<'a>(&self, entry: &'a Entry) -> Option<&'a str> {
+        match self {
+            Field::Title => entry.get_title(),
+            Field::Username => entry.get_username(),
+            Field::Password => entry.get_password(),
+            Field::Url => entry.get_url(),
+            Field::Notes => entry.get("Notes"),
+        }
+    }
+}
+
+/// JSON-serializable view of a matched entry, including its full group
+/// path so results can be told apart when piped to `jq`.
+#[derive(Serialize)]
+struct EntryJson {
+    title: String,
+    username: String,
+    password: String,
+    url: String,
+    notes: String,
+    group: String,
+}
+
+impl From<&FoundEntry<'_>> for EntryJson {
+    fn from(found: &FoundEntry<'_>) -> Self {
+        EntryJson {
+            title: found.entry.get_title().unwrap_or_default().to_string(),
+            username: found.entry.get_username().unwrap_or_default().to_string(),
+            password: found.entry.get_password().unwrap_or_default().to_string(),
+            url: found.entry.get_url().unwrap_or_default().to_string(),
+            notes: found.entry.get("Notes").unwrap_or_default().to_string(),
+            group: join_group_path(&found.group_path),
+        }
+    }
+}
+
+/// Joins a group path (root-to-leaf) into the slash-separated string used
+/// for the `group` field of [`EntryJson`].
+fn join_group_path(path: &[&str]) -> String {
+    path.join("/")
+}
+
+/// Prints `entries` as a JSON array to stdout.
+pub fn print_json(entries: &[FoundEntry]) -> serde_json::Result<()> {
+    let values: Vec<EntryJson> = entries.iter().map(EntryJson::from).collect();
+    println!("{}", serde_json::to_string_pretty(&values)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_path_joins_with_slashes() {
+        assert_eq!(join_group_path(&["Root", "Work", "Email"]), "Root/Work/Email");
+    }
+
+    #[test]
+    fn empty_group_path_joins_to_empty_string() {
+        assert_eq!(join_group_path(&[]), "");
+    }
+}
@@ -0,0 +1,110 @@
+use keepass::{Entry, Group, Node};
+use regex::Regex;
+
+/// Which entry fields are considered when matching a search query.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchFields {
+    pub username: bool,
+    pub url: bool,
+    pub notes: bool,
+}
+
+/// Matches a query against a field value, either as a case-insensitive
+/// substring or as a regular expression.
+pub enum Matcher {
+    Substring(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    pub fn new(query: &str, regex: bool) -> Result<Matcher, regex::Error> {
+        if regex {
+            Ok(Matcher::Regex(Regex::new(query)?))
+        } else {
+            Ok(Matcher::Substring(query.to_lowercase()))
+        }
+    }
+
+    fn is_match(&self, value: &str) -> bool {
+        match self {
+            Matcher::Substring(needle) => value.to_lowercase().contains(needle.as_str()),
+            Matcher::Regex(re) => re.is_match(value),
+        }
+    }
+}
+
+/// An entry matched by [`find_entries`], together with the path of group
+/// names leading down to it from the root.
+pub struct FoundEntry<'a> {
+    pub entry: &'a Entry,
+    pub group_path: Vec<&'a str>,
+}
+
+/// Walks the whole tree rooted at `root` and returns every entry whose
+/// title, or any field enabled by `fields`, matches `matcher`.
+pub fn find_entries<'a>(matcher: &Matcher, fields: SearchFields, root: &'a Group) -> Vec<FoundEntry<'a>> {
+    let mut result = vec![];
+    let mut path = vec![];
+    walk(matcher, fields, root, &mut path, &mut result);
+    result
+}
+
+fn walk<'a>(
+    matcher: &Matcher,
+    fields: SearchFields,
+    group: &'a Group,
+    path: &mut Vec<&'a str>,
+    result: &mut Vec<FoundEntry<'a>>,
+) {
+    for child in &group.children {
+        match child {
+            Node::Group(g) => {
+                path.push(&g.name);
+                walk(matcher, fields, g, path, result);
+                path.pop();
+            }
+            Node::Entry(entry) => {
+                if matches(matcher, fields, entry) {
+                    result.push(FoundEntry {
+                        entry,
+                        group_path: path.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn matches(matcher: &Matcher, fields: SearchFields, entry: &Entry) -> bool {
+    let matches_field = |value: Option<&str>| value.is_some_and(|v| matcher.is_match(v));
+
+    matches_field(entry.get_title())
+        || (fields.username && matches_field(entry.get_username()))
+        || (fields.url && matches_field(entry.get_url()))
+        || (fields.notes && matches_field(entry.get("Notes")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substring_match_is_case_insensitive() {
+        let matcher = Matcher::new("github", false).unwrap();
+        assert!(matcher.is_match("GitHub Login"));
+        assert!(!matcher.is_match("GitLab Login"));
+    }
+
+    #[test]
+    fn regex_match_compiles_and_matches() {
+        let matcher = Matcher::new("^git(hub|lab)$", true).unwrap();
+        assert!(matcher.is_match("github"));
+        assert!(!matcher.is_match("GitHub"));
+        assert!(!matcher.is_match("bitbucket"));
+    }
+
+    #[test]
+    fn invalid_regex_is_rejected() {
+        assert!(Matcher::new("(unclosed", true).is_err());
+    }
+}